@@ -0,0 +1,114 @@
+//! A minimal encoder/decoder for the native CQL binary protocol (v4).
+//!
+//! Only the pieces the transport needs are modelled here: request framing via
+//! [`Serialize`], the response types the multiplex layer hands back, and the
+//! CQL primitive encoders shared by every request body.
+
+use bytes::{BytesMut, BufMut};
+
+pub mod requests;
+pub mod responses;
+
+/// Protocol version byte stamped on every request frame (v4, with the 0x80
+/// response direction bit clear).
+pub const PROTOCOL_VERSION: u8 = 0x04;
+
+/// Serialize a request into the bytes of a complete CQL frame.
+pub trait Serialize {
+    /// Pack `self` as a framed CQL command, header included, appending the
+    /// bytes straight onto `dst` so a queued burst of requests shares one
+    /// allocation rather than one `Vec` per command.
+    fn serialize(&self, dst: &mut BytesMut);
+}
+
+/// Decode a value from the body of a CQL frame.
+pub trait Parse: Sized {
+    fn parse(body: &[u8]) -> ParseResult<Self>;
+}
+
+/// Result of a parse attempt.
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// Reasons a frame body could not be decoded.
+#[derive(Debug)]
+pub enum ParseError {
+    /// The buffer ended before a complete frame was available.
+    Incomplete,
+    /// The frame was structurally invalid.
+    Malformed(&'static str),
+}
+
+/// Write a CQL request frame for `opcode` onto `dst`, with the body produced
+/// in place by `write_body`.
+///
+/// The 4-byte length is stamped with a placeholder before the body is written
+/// and backfilled afterwards, so the whole frame lands in `dst` without a
+/// scratch buffer. The stream id is fixed at 0: the transport does not yet
+/// reuse stream ids to multiplex concurrent requests.
+pub fn serialize_frame<F>(dst: &mut BytesMut, opcode: u8, write_body: F)
+    where F: FnOnce(&mut BytesMut),
+{
+    dst.reserve(9);
+    dst.put_u8(PROTOCOL_VERSION);
+    dst.put_u8(0x00); // flags
+    dst.put_u8(0x00); // stream id, high byte
+    dst.put_u8(0x00); // stream id, low byte
+    dst.put_u8(opcode);
+
+    let len_pos = dst.len();
+    dst.put_u32_be(0); // length placeholder, backfilled below
+    let body_start = dst.len();
+
+    write_body(dst);
+
+    let body_len = (dst.len() - body_start) as u32;
+    dst[len_pos] = (body_len >> 24) as u8;
+    dst[len_pos + 1] = (body_len >> 16) as u8;
+    dst[len_pos + 2] = (body_len >> 8) as u8;
+    dst[len_pos + 3] = body_len as u8;
+}
+
+// -- CQL primitive encoders -------------------------------------------------
+
+pub fn write_int(buf: &mut BytesMut, v: i32) {
+    buf.put_i32_be(v);
+}
+
+pub fn write_short(buf: &mut BytesMut, v: u16) {
+    buf.put_u16_be(v);
+}
+
+pub fn write_long_string(buf: &mut BytesMut, s: &str) {
+    write_int(buf, s.len() as i32);
+    buf.put_slice(s.as_bytes());
+}
+
+pub fn write_bytes(buf: &mut BytesMut, b: &[u8]) {
+    write_int(buf, b.len() as i32);
+    buf.put_slice(b);
+}
+
+pub fn write_short_bytes(buf: &mut BytesMut, b: &[u8]) {
+    write_short(buf, b.len() as u16);
+    buf.put_slice(b);
+}
+
+pub fn write_string_map(buf: &mut BytesMut, entries: &[(String, String)]) {
+    write_short(buf, entries.len() as u16);
+    for &(ref k, ref v) in entries {
+        write_string(buf, k);
+        write_string(buf, v);
+    }
+}
+
+pub fn write_string(buf: &mut BytesMut, s: &str) {
+    write_short(buf, s.len() as u16);
+    buf.put_slice(s.as_bytes());
+}
+
+pub fn write_string_list(buf: &mut BytesMut, items: &[String]) {
+    write_short(buf, items.len() as u16);
+    for item in items {
+        write_string(buf, item);
+    }
+}
@@ -0,0 +1,234 @@
+//! Request frames the transport packs and writes to the socket.
+
+use bytes::{BytesMut, BufMut};
+
+use super::{Serialize, serialize_frame};
+use super::{write_short, write_long_string, write_bytes, write_short_bytes,
+            write_string_map, write_string_list};
+
+/// CQL request opcodes.
+const OPCODE_STARTUP: u8 = 0x01;
+const OPCODE_OPTIONS: u8 = 0x05;
+const OPCODE_QUERY: u8 = 0x07;
+const OPCODE_PREPARE: u8 = 0x09;
+const OPCODE_EXECUTE: u8 = 0x0A;
+const OPCODE_REGISTER: u8 = 0x0B;
+const OPCODE_BATCH: u8 = 0x0D;
+const OPCODE_AUTH_RESPONSE: u8 = 0x0F;
+
+/// Tunable consistency level attached to a query or batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Consistency {
+    Any,
+    One,
+    Two,
+    Three,
+    Quorum,
+    All,
+    LocalQuorum,
+    EachQuorum,
+    Serial,
+    LocalSerial,
+    LocalOne,
+}
+
+impl Consistency {
+    /// The wire code for this level.
+    pub fn code(&self) -> u16 {
+        match *self {
+            Consistency::Any => 0x0000,
+            Consistency::One => 0x0001,
+            Consistency::Two => 0x0002,
+            Consistency::Three => 0x0003,
+            Consistency::Quorum => 0x0004,
+            Consistency::All => 0x0005,
+            Consistency::LocalQuorum => 0x0006,
+            Consistency::EachQuorum => 0x0007,
+            Consistency::Serial => 0x0008,
+            Consistency::LocalSerial => 0x0009,
+            Consistency::LocalOne => 0x000A,
+        }
+    }
+}
+
+/// `OPTIONS` — ask the server which startup options it supports.
+pub struct Options;
+
+impl Serialize for Options {
+    fn serialize(&self, dst: &mut BytesMut) {
+        serialize_frame(dst, OPCODE_OPTIONS, |_| {});
+    }
+}
+
+/// `STARTUP` — initialise the connection with a set of options.
+pub struct Startup {
+    pub options: Vec<(String, String)>,
+}
+
+impl Startup {
+    /// A `STARTUP` requesting CQL 3.0.0 with no compression.
+    pub fn new() -> Startup {
+        Startup { options: vec![("CQL_VERSION".to_string(), "3.0.0".to_string())] }
+    }
+}
+
+impl Serialize for Startup {
+    fn serialize(&self, dst: &mut BytesMut) {
+        serialize_frame(dst, OPCODE_STARTUP, |body| {
+            write_string_map(body, &self.options);
+        });
+    }
+}
+
+/// `QUERY` — a single CQL statement executed at `consistency`.
+pub struct Query {
+    pub query: String,
+    pub consistency: Consistency,
+}
+
+impl Serialize for Query {
+    fn serialize(&self, dst: &mut BytesMut) {
+        serialize_frame(dst, OPCODE_QUERY, |body| {
+            write_long_string(body, &self.query);
+            write_short(body, self.consistency.code());
+            body.put_u8(0x00); // query flags: no bound values, protocol defaults
+        });
+    }
+}
+
+/// `AUTH_RESPONSE` — a SASL token answering an `AUTHENTICATE`/`AUTH_CHALLENGE`.
+pub struct AuthResponse {
+    pub token: Vec<u8>,
+}
+
+impl AuthResponse {
+    /// Wrap a raw SASL token.
+    pub fn new(token: Vec<u8>) -> AuthResponse {
+        AuthResponse { token: token }
+    }
+}
+
+impl Serialize for AuthResponse {
+    fn serialize(&self, dst: &mut BytesMut) {
+        serialize_frame(dst, OPCODE_AUTH_RESPONSE, |body| {
+            write_bytes(body, &self.token);
+        });
+    }
+}
+
+/// Write a `[short]`-prefixed list of `[bytes]` bound values.
+fn write_values(body: &mut BytesMut, values: &[Vec<u8>]) {
+    write_short(body, values.len() as u16);
+    for value in values {
+        write_bytes(body, value);
+    }
+}
+
+/// `PREPARE` — ask the server to prepare a statement and return its id.
+pub struct Prepare {
+    pub query: String,
+}
+
+impl Serialize for Prepare {
+    fn serialize(&self, dst: &mut BytesMut) {
+        serialize_frame(dst, OPCODE_PREPARE, |body| {
+            write_long_string(body, &self.query);
+        });
+    }
+}
+
+/// `EXECUTE` — run a previously prepared statement.
+///
+/// `id` is the opaque identifier handed back in the `Prepared` response, which
+/// is how a follow-up execution is correlated with its preparation.
+pub struct Execute {
+    pub id: Vec<u8>,
+    pub values: Vec<Vec<u8>>,
+    pub consistency: Consistency,
+}
+
+impl Serialize for Execute {
+    fn serialize(&self, dst: &mut BytesMut) {
+        serialize_frame(dst, OPCODE_EXECUTE, |body| {
+            write_short_bytes(body, &self.id);
+            write_short(body, self.consistency.code());
+            // Query flags: 0x01 signals that bound values follow.
+            if self.values.is_empty() {
+                body.put_u8(0x00);
+            } else {
+                body.put_u8(0x01);
+                write_values(body, &self.values);
+            }
+        });
+    }
+}
+
+/// `REGISTER` — subscribe to the named server event types.
+pub struct Register {
+    pub events: Vec<String>,
+}
+
+impl Serialize for Register {
+    fn serialize(&self, dst: &mut BytesMut) {
+        serialize_frame(dst, OPCODE_REGISTER, |body| {
+            write_string_list(body, &self.events);
+        });
+    }
+}
+
+/// Whether a batch is logged, unlogged, or a counter batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchType {
+    Logged,
+    Unlogged,
+    Counter,
+}
+
+impl BatchType {
+    fn code(&self) -> u8 {
+        match *self {
+            BatchType::Logged => 0x00,
+            BatchType::Unlogged => 0x01,
+            BatchType::Counter => 0x02,
+        }
+    }
+}
+
+/// A single statement within a `BATCH`: either a simple query string or a
+/// prepared statement id, each with its own bound values.
+pub enum BatchStatement {
+    Query(String, Vec<Vec<u8>>),
+    Prepared(Vec<u8>, Vec<Vec<u8>>),
+}
+
+/// `BATCH` — execute several simple or prepared statements atomically.
+pub struct Batch {
+    pub batch_type: BatchType,
+    pub statements: Vec<BatchStatement>,
+    pub consistency: Consistency,
+}
+
+impl Serialize for Batch {
+    fn serialize(&self, dst: &mut BytesMut) {
+        serialize_frame(dst, OPCODE_BATCH, |body| {
+            body.put_u8(self.batch_type.code());
+            write_short(body, self.statements.len() as u16);
+            for statement in &self.statements {
+                match *statement {
+                    BatchStatement::Query(ref query, ref values) => {
+                        body.put_u8(0x00); // kind: simple query string
+                        write_long_string(body, query);
+                        write_values(body, values);
+                    }
+                    BatchStatement::Prepared(ref id, ref values) => {
+                        body.put_u8(0x01); // kind: prepared statement id
+                        write_short_bytes(body, id);
+                        write_values(body, values);
+                    }
+                }
+            }
+            write_short(body, self.consistency.code());
+            body.put_u8(0x00); // batch flags: protocol defaults
+        });
+    }
+}
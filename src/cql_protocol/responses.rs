@@ -0,0 +1,48 @@
+//! Response frames the transport decodes and hands to the dispatcher.
+
+/// `ERROR` — a server-side error with its CQL error code and message.
+pub struct Error {
+    pub code: i32,
+    pub message: String,
+}
+
+/// `AUTHENTICATE` — the server demands SASL auth via this authenticator class.
+pub struct Authenticate {
+    pub authenticator: String,
+}
+
+/// `AUTH_CHALLENGE` — an intermediate SASL token from the server.
+pub struct AuthChallenge {
+    pub token: Vec<u8>,
+}
+
+/// `AUTH_SUCCESS` — authentication completed, with an optional final token.
+pub struct AuthSuccess {
+    pub token: Option<Vec<u8>>,
+}
+
+/// `SUPPORTED` — the startup options the server understands.
+pub struct Supported {
+    pub options: Vec<(String, Vec<String>)>,
+}
+
+/// `RESULT` of kind `Void`/`Rows`/... — modelled opaquely by kind for now.
+pub struct Result {
+    pub kind: i32,
+}
+
+/// `RESULT` of kind `SetKeyspace`.
+pub struct SetKeyspace {
+    pub keyspace: String,
+}
+
+/// `RESULT` of kind `Prepared` — carries the statement id reused by `Execute`.
+pub struct Prepared {
+    pub id: Vec<u8>,
+}
+
+/// `RESULT` of kind `SchemaChange` (shares its shape with the `EVENT` frame).
+pub struct SchemaChange {
+    pub change_type: String,
+    pub target: String,
+}
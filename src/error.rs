@@ -0,0 +1,46 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Errors surfaced by the CQL transport and driver.
+#[derive(Debug)]
+pub enum CqlError {
+    /// An error from the underlying socket or TLS session.
+    Io(io::Error),
+    /// The server's advertised authenticator class is not one this client
+    /// knows how to satisfy. Carries the class name the server sent.
+    UnsupportedAuthenticator(String),
+}
+
+impl fmt::Display for CqlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CqlError::Io(ref e) => write!(f, "io error: {}", e),
+            CqlError::UnsupportedAuthenticator(ref class) => {
+                write!(f, "unsupported authenticator: {}", class)
+            }
+        }
+    }
+}
+
+impl Error for CqlError {
+    fn description(&self) -> &str {
+        match *self {
+            CqlError::Io(ref e) => e.description(),
+            CqlError::UnsupportedAuthenticator(..) => "unsupported authenticator",
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            CqlError::Io(ref e) => Some(e),
+            CqlError::UnsupportedAuthenticator(..) => None,
+        }
+    }
+}
+
+impl From<io::Error> for CqlError {
+    fn from(e: io::Error) -> CqlError {
+        CqlError::Io(e)
+    }
+}
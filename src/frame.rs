@@ -0,0 +1,18 @@
+//! The multiplex frame the transport reads and writes.
+//!
+//! `tokio-proto`'s own `multiplex::Frame` has drifted incompatibly across
+//! releases, so the transport carries its own copy of the small enum it needs
+//! — a request/response message, an optional streamed body, an error, or an
+//! end-of-stream marker.
+pub enum Frame<T, B, E> {
+    /// A complete request or response message.
+    Message(T),
+    /// A message that will be followed by body frames.
+    MessageWithBody(T, B),
+    /// A streamed body chunk; `None` ends the stream.
+    Body(Option<B>),
+    /// A transport-level error.
+    Error(E),
+    /// End of stream: the peer has closed the connection.
+    Done,
+}
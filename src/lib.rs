@@ -0,0 +1,33 @@
+//! A CQL (Cassandra) protocol transport built on `tokio-core`'s `FramedIo`.
+
+// The transport is written against `tokio_core::io::{Io, FramedIo}`, which the
+// crate still exposes but marks deprecated in favour of `tokio-io`.
+#![allow(deprecated)]
+
+// This crate is written in the 2015 idiom it was born in; several later style
+// lints flag that idiom (explicit struct field names, trailing `return`,
+// `io::Error::new`, `&ref` bindings) and are quietened here rather than
+// churning every module into a newer dialect.
+#![allow(bare_trait_objects)]
+#![allow(clippy::redundant_field_names)]
+#![allow(clippy::needless_return)]
+#![allow(clippy::io_other_error)]
+#![allow(clippy::needless_borrowed_reference)]
+#![allow(clippy::new_without_default)]
+
+extern crate bytes;
+extern crate futures;
+#[macro_use]
+extern crate log;
+extern crate tokio_core;
+
+#[cfg(feature = "tls")]
+extern crate rustls;
+#[cfg(feature = "tls")]
+extern crate tokio_rustls;
+
+pub mod cql_protocol;
+pub mod error;
+pub mod frame;
+pub mod pipe;
+pub mod transport;
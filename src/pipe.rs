@@ -0,0 +1,142 @@
+use tokio_core::io::Io;
+use futures::Async;
+use std::cell::RefCell;
+use std::cmp;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::rc::Rc;
+
+/// One direction of the pipe: a byte queue plus the switches that let a test
+/// drive readiness by hand.
+struct Buffer {
+    data: VecDeque<u8>,
+    // When set, reads observe end-of-stream (`Ok(0)`) once `data` is drained.
+    eof: bool,
+    // When set, the corresponding I/O call yields `WouldBlock` regardless of
+    // how much data is buffered.
+    block: bool,
+}
+
+impl Buffer {
+    fn new() -> Buffer {
+        Buffer { data: VecDeque::new(), eof: false, block: false }
+    }
+}
+
+/// An in-memory, single-threaded duplex `Io`, analogous to a socket pair.
+///
+/// A `Pipe` reads from one shared [`Buffer`] and writes to another; the peer
+/// returned alongside it has the two swapped, so bytes written to one end
+/// surface on the other. Tests can also reach into a single end directly:
+/// push raw CQL response bytes onto the read side with [`push`](Pipe::push) so
+/// [`CqlTransport::read`] parses them, drain the write side with
+/// [`drain`](Pipe::drain) to inspect `get_packed_command` output, and toggle
+/// [`block`](Pipe::block_reads)/[`eof`](Pipe::close) to exercise the partial
+/// frame and connection-close code paths.
+#[derive(Clone)]
+pub struct Pipe {
+    rd: Rc<RefCell<Buffer>>,
+    wr: Rc<RefCell<Buffer>>,
+}
+
+/// Create a connected pair of pipe ends.
+pub fn pipe() -> (Pipe, Pipe) {
+    let a = Rc::new(RefCell::new(Buffer::new()));
+    let b = Rc::new(RefCell::new(Buffer::new()));
+
+    let client = Pipe { rd: a.clone(), wr: b.clone() };
+    let server = Pipe { rd: b, wr: a };
+
+    (client, server)
+}
+
+impl Pipe {
+    /// Push bytes onto this end's read side, as if the peer had sent them.
+    pub fn push(&self, bytes: &[u8]) {
+        self.rd.borrow_mut().data.extend(bytes.iter().cloned());
+    }
+
+    /// Drain everything written to this end, as the peer would observe it.
+    pub fn drain(&self) -> Vec<u8> {
+        let mut buf = self.wr.borrow_mut();
+        buf.data.drain(..).collect()
+    }
+
+    /// Mark the read side as closed; once buffered bytes are consumed, reads
+    /// return `Ok(0)`.
+    pub fn close(&self) {
+        self.rd.borrow_mut().eof = true;
+    }
+
+    /// Inject (or clear) a `WouldBlock` on reads, to split a frame across
+    /// multiple `read` calls.
+    pub fn block_reads(&self, block: bool) {
+        self.rd.borrow_mut().block = block;
+    }
+
+    /// Inject (or clear) a `WouldBlock` on writes.
+    pub fn block_writes(&self, block: bool) {
+        self.wr.borrow_mut().block = block;
+    }
+}
+
+impl Read for Pipe {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut rd = self.rd.borrow_mut();
+
+        if rd.block {
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, "pipe read blocked"));
+        }
+
+        if rd.data.is_empty() {
+            if rd.eof {
+                return Ok(0);
+            }
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, "pipe read empty"));
+        }
+
+        let n = cmp::min(buf.len(), rd.data.len());
+        for slot in buf.iter_mut().take(n) {
+            *slot = rd.data.pop_front().expect("buffer non-empty");
+        }
+        Ok(n)
+    }
+}
+
+impl Write for Pipe {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut wr = self.wr.borrow_mut();
+
+        if wr.block {
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, "pipe write blocked"));
+        }
+
+        wr.data.extend(buf.iter().cloned());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Io for Pipe {
+    fn poll_read(&mut self) -> Async<()> {
+        let rd = self.rd.borrow();
+        if rd.block {
+            Async::NotReady
+        } else if !rd.data.is_empty() || rd.eof {
+            Async::Ready(())
+        } else {
+            Async::NotReady
+        }
+    }
+
+    fn poll_write(&mut self) -> Async<()> {
+        if self.wr.borrow().block {
+            Async::NotReady
+        } else {
+            Async::Ready(())
+        }
+    }
+}